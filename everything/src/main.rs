@@ -1,11 +0,0 @@
-mod connection;
-mod constants;
-mod core;
-mod request;
-
-fn main() -> Result<(), std::io::Error> {
-    let core = core::Core::start();
-    connection::Connection::start(core.get_sender())?;
-
-    Ok(())
-}
\ No newline at end of file
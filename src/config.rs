@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::fs;
+
+use crate::constants;
+use crate::core;
+
+const KEY_BIND_ADDR: &str = "bind_addr";
+const KEY_SHARD_COUNT: &str = "shard_count";
+const KEY_DEFAULT_INCR_STEP: &str = "default_incr_step";
+const KEY_SNAPSHOT_PATH: &str = "snapshot_path";
+
+const KNOWN_KEYS: &[&str] = &[
+    KEY_BIND_ADDR,
+    KEY_SHARD_COUNT,
+    KEY_DEFAULT_INCR_STEP,
+    KEY_SNAPSHOT_PATH,
+];
+
+#[derive(Debug, PartialEq)]
+pub enum ConfigError {
+    UnknownKey(String),
+    InvalidValue { key: String, value: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::UnknownKey(key) => write!(f, "unknown config key: {key}"),
+            ConfigError::InvalidValue { key, value } => {
+                write!(f, "invalid value for {key}: {value}")
+            }
+        }
+    }
+}
+
+/// Server configuration, resolved from defaults, an optional config file,
+/// and environment overrides, in that precedence order (later sources win).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub bind_addr: String,
+    pub shard_count: usize,
+    pub default_incr_step: i64,
+    pub snapshot_path: String,
+}
+
+impl Config {
+    pub fn load(file_path: Option<&str>) -> Result<Self, ConfigError> {
+        let mut resolved = defaults();
+
+        if let Some(path) = file_path {
+            resolved.extend(file_layer(path));
+        }
+
+        resolved.extend(env_layer());
+
+        for key in resolved.keys() {
+            if !KNOWN_KEYS.contains(&key.as_str()) {
+                return Err(ConfigError::UnknownKey(key.clone()));
+            }
+        }
+
+        let shard_count = parse_value(&resolved, KEY_SHARD_COUNT)?;
+        let default_incr_step = parse_value(&resolved, KEY_DEFAULT_INCR_STEP)?;
+
+        Ok(Config {
+            bind_addr: resolved[KEY_BIND_ADDR].clone(),
+            shard_count,
+            default_incr_step,
+            snapshot_path: resolved[KEY_SNAPSHOT_PATH].clone(),
+        })
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(
+    resolved: &HashMap<String, String>,
+    key: &str,
+) -> Result<T, ConfigError> {
+    resolved[key]
+        .parse()
+        .map_err(|_| ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: resolved[key].clone(),
+        })
+}
+
+fn defaults() -> HashMap<String, String> {
+    let mut layer = HashMap::new();
+    layer.insert(KEY_BIND_ADDR.to_string(), constants::BIND_ADDR.to_string());
+    layer.insert(
+        KEY_SHARD_COUNT.to_string(),
+        core::SHARD_COUNT.to_string(),
+    );
+    layer.insert(
+        KEY_DEFAULT_INCR_STEP.to_string(),
+        constants::DEFAULT_INCR_STEP.to_string(),
+    );
+    layer.insert(
+        KEY_SNAPSHOT_PATH.to_string(),
+        constants::SNAPSHOT_PATH.to_string(),
+    );
+    layer
+}
+
+// Config files are simple `key = value` lines; blank lines and `#` comments
+// are ignored. A missing file just yields an empty layer.
+fn file_layer(path: &str) -> HashMap<String, String> {
+    let mut layer = HashMap::new();
+    let Ok(contents) = fs::read_to_string(path) else {
+        return layer;
+    };
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            layer.insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+
+    layer
+}
+
+// Overrides are read from `RUSTDSS_<KEY>` environment variables.
+fn env_layer() -> HashMap<String, String> {
+    let mut layer = HashMap::new();
+    for key in KNOWN_KEYS {
+        let env_key = format!("RUSTDSS_{}", key.to_uppercase());
+        if let Ok(value) = env::var(env_key) {
+            layer.insert((*key).to_string(), value);
+        }
+    }
+    layer
+}
+
+#[cfg(test)]
+mod should {
+    use super::*;
+
+    #[test]
+    fn defaults_are_used_when_no_file_or_env_are_set() {
+        let config = Config::load(None).unwrap();
+
+        assert_eq!(config.bind_addr, constants::BIND_ADDR);
+        assert_eq!(config.shard_count, core::SHARD_COUNT);
+        assert_eq!(config.default_incr_step, constants::DEFAULT_INCR_STEP);
+        assert_eq!(config.snapshot_path, constants::SNAPSHOT_PATH);
+    }
+
+    #[test]
+    fn file_values_override_defaults() {
+        let path = std::env::temp_dir().join("rustdss-config-file-override.conf");
+        let path = path.to_str().unwrap().to_string();
+        fs::write(&path, "bind_addr = 0.0.0.0:7000\nshard_count = 4\n").unwrap();
+
+        let config = Config::load(Some(&path)).unwrap();
+
+        assert_eq!(config.bind_addr, "0.0.0.0:7000");
+        assert_eq!(config.shard_count, 4);
+        assert_eq!(config.default_incr_step, constants::DEFAULT_INCR_STEP);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn env_vars_override_file_values() {
+        let path = std::env::temp_dir().join("rustdss-config-env-override.conf");
+        let path = path.to_str().unwrap().to_string();
+        fs::write(&path, "bind_addr = 0.0.0.0:7000\nshard_count = 4\n").unwrap();
+
+        env::set_var("RUSTDSS_BIND_ADDR", "0.0.0.0:9000");
+        env::set_var("RUSTDSS_SHARD_COUNT", "8");
+
+        let config = Config::load(Some(&path)).unwrap();
+
+        assert_eq!(config.bind_addr, "0.0.0.0:9000");
+        assert_eq!(config.shard_count, 8);
+
+        env::remove_var("RUSTDSS_BIND_ADDR");
+        env::remove_var("RUSTDSS_SHARD_COUNT");
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn unknown_keys_in_a_file_are_rejected() {
+        let path = std::env::temp_dir().join("rustdss-config-file-unknown-key.conf");
+        let path = path.to_str().unwrap().to_string();
+        fs::write(&path, "not_a_real_key = 1\n").unwrap();
+
+        let result = Config::load(Some(&path));
+
+        assert_eq!(result, Err(ConfigError::UnknownKey("not_a_real_key".into())));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        let path = std::env::temp_dir().join("rustdss-config-file-invalid-value.conf");
+        let path = path.to_str().unwrap().to_string();
+        fs::write(&path, "shard_count = not-a-number\n").unwrap();
+
+        let result = Config::load(Some(&path));
+
+        assert_eq!(
+            result,
+            Err(ConfigError::InvalidValue {
+                key: KEY_SHARD_COUNT.into(),
+                value: "not-a-number".into(),
+            })
+        );
+
+        fs::remove_file(path).unwrap();
+    }
+}
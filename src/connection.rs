@@ -0,0 +1,129 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+use crate::config::Config;
+use crate::core::{Command, CoreError};
+use crate::request;
+use crate::transport::RespData;
+
+pub struct Connection;
+
+impl Connection {
+    /// Binds to `config.bind_addr` and accepts connections, blocking the
+    /// calling thread for as long as the server runs, so it is the last thing
+    /// `main` calls (unlike `Core::start`, which spawns the core thread and
+    /// returns at once). Each connection is handled on
+    /// its own thread, reading one command per line and dispatching it to
+    /// the core thread via `sender`.
+    pub fn start(
+        sender: Sender<(Command, Sender<Result<RespData, CoreError>>)>,
+        config: &Config,
+    ) -> io::Result<()> {
+        let listener = TcpListener::bind(&config.bind_addr)?;
+
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else { continue };
+            let sender = sender.clone();
+            thread::spawn(move || {
+                let _ = handle_connection(stream, sender);
+            });
+        }
+
+        Ok(())
+    }
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    sender: Sender<(Command, Sender<Result<RespData, CoreError>>)>,
+) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let reader = BufReader::new(stream);
+
+    for line in reader.lines() {
+        let response = match dispatch(&sender, &line?) {
+            Some(result) => result.unwrap_or_else(core_error_to_resp),
+            None => break,
+        };
+        writer.write_all(response.to_resp_string().as_bytes())?;
+    }
+
+    Ok(())
+}
+
+// Parses and runs one line of input against the core thread. Returns `None`
+// if the core thread is gone, which ends this connection.
+fn dispatch(
+    sender: &Sender<(Command, Sender<Result<RespData, CoreError>>)>,
+    line: &str,
+) -> Option<Result<RespData, CoreError>> {
+    let cmd = match request::parse(line) {
+        Ok(cmd) => cmd,
+        Err(err) => return Some(Err(err)),
+    };
+
+    let (reply_sender, reply_receiver) = mpsc::channel();
+    sender.send((cmd, reply_sender)).ok()?;
+    reply_receiver.recv().ok()
+}
+
+/// Formats a `CoreError` as the RESP error string a client would see. The
+/// core thread sends back the raw `Result<RespData, CoreError>` from
+/// `core_logic` unmapped; `handle_connection` calls this to turn that
+/// semantic error into the transport-level reply it writes back.
+pub fn core_error_to_resp(err: CoreError) -> RespData {
+    match err {
+        CoreError::NotANumber => RespData::Error("NaN".into()),
+        CoreError::Overflow => RespData::Error("ERR increment or decrement would overflow".into()),
+        CoreError::UnknownCommand => RespData::Error("ERR unknown command".into()),
+        CoreError::NoActiveTransaction => RespData::Error("ERR EXEC/DISCARD without MULTI".into()),
+        CoreError::Persistence(message) => RespData::Error(format!("ERR {message}")),
+    }
+}
+
+#[cfg(test)]
+mod should {
+    use super::*;
+
+    #[test]
+    fn not_a_number_becomes_a_nan_error() {
+        assert_eq!(
+            core_error_to_resp(CoreError::NotANumber),
+            RespData::Error("NaN".into())
+        );
+    }
+
+    #[test]
+    fn overflow_becomes_an_overflow_error() {
+        assert_eq!(
+            core_error_to_resp(CoreError::Overflow),
+            RespData::Error("ERR increment or decrement would overflow".into())
+        );
+    }
+
+    #[test]
+    fn unknown_command_becomes_an_unknown_command_error() {
+        assert_eq!(
+            core_error_to_resp(CoreError::UnknownCommand),
+            RespData::Error("ERR unknown command".into())
+        );
+    }
+
+    #[test]
+    fn no_active_transaction_becomes_a_no_active_transaction_error() {
+        assert_eq!(
+            core_error_to_resp(CoreError::NoActiveTransaction),
+            RespData::Error("ERR EXEC/DISCARD without MULTI".into())
+        );
+    }
+
+    #[test]
+    fn persistence_becomes_the_underlying_message_prefixed_with_err() {
+        assert_eq!(
+            core_error_to_resp(CoreError::Persistence("disk full".into())),
+            RespData::Error("ERR disk full".into())
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub const BIND_ADDR: &str = "127.0.0.1:6379";
+pub const DEFAULT_INCR_STEP: i64 = 1;
+pub const SNAPSHOT_PATH: &str = "rustdss.snapshot";
+pub const CONFIG_PATH: &str = "rustdss.conf";
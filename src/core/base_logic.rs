@@ -1,213 +1,533 @@
-use super::{Command, CoreState};
+use std::collections::HashMap;
+
+use rayon::prelude::*;
+
+use super::persistence;
+use super::{Command, CoreError, CoreState};
 use crate::transport::RespData;
 
-pub fn core_logic(state: &mut CoreState, cmd: Command) -> RespData {
-    let response = match cmd {
+// Records `key`'s current value into the topmost checkpoint, but only the
+// first time it's touched within that checkpoint, so a later Discard can
+// restore exactly what Multi saw.
+fn record_original(state: &mut CoreState, key: &str) {
+    let prior = state.get(key).cloned();
+    if let Some(checkpoint) = state.checkpoints.last_mut() {
+        checkpoint.entry(key.to_string()).or_insert(prior);
+    }
+}
+
+// A single `*` wildcard is all KEYS needs to support here; `a*` matches any
+// key starting with `a`, `*` matches everything.
+fn glob_match(pattern: &str, key: &str) -> bool {
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => {
+            key.len() >= prefix.len() + suffix.len()
+                && key.starts_with(prefix)
+                && key.ends_with(suffix)
+        }
+        None => pattern == key,
+    }
+}
+
+pub fn core_logic(state: &mut CoreState, cmd: Command) -> Result<RespData, CoreError> {
+    match cmd {
         Command::Set(key, value) => {
-            state.keyval.insert(key, value);
-            RespData::ok()
+            record_original(state, &key);
+            state.insert(key, value);
+            Ok(RespData::ok())
         }
-        Command::Get(key) => state
-            .keyval
+        Command::Get(key) => Ok(state
             .get(&key)
             .unwrap_or(&RespData::Error("(nil)".into()))
-            .clone(),
+            .clone()),
         Command::FlushAll => {
-            state.keyval.clear();
-            RespData::ok()
+            if state.checkpoints.last().is_some() {
+                let keys: Vec<String> = state
+                    .shards
+                    .iter()
+                    .flat_map(|shard| shard.keys().cloned())
+                    .collect();
+                for key in &keys {
+                    record_original(state, key);
+                }
+            }
+            state.shards.par_iter_mut().for_each(|shard| shard.clear());
+            Ok(RespData::ok())
         }
         Command::Incr(key, maybe_by) => {
-            let prev = state.keyval.get(&key);
-
-            let op = match prev {
-                Some(RespData::Number(val)) => Ok(RespData::Number(val + maybe_by.unwrap_or(1))),
-                Some(_) => Err(RespData::Error("NaN".into())),
-                None => Ok(RespData::Number(1)),
+            let by = maybe_by.unwrap_or(state.default_incr_step);
+            let current = match state.get(&key) {
+                Some(RespData::Number(val)) => *val,
+                Some(_) => return Err(CoreError::NotANumber),
+                None => 0,
             };
+            let new_val = current.checked_add(by).ok_or(CoreError::Overflow)?;
 
-            if let Ok(new_val) = op {
-                state.keyval.insert(key, new_val.clone());
-                new_val
-            } else {
-                op.err().unwrap()
-            }
+            record_original(state, &key);
+            state.insert(key, RespData::Number(new_val));
+            Ok(RespData::Number(new_val))
         }
         Command::Decr(key, maybe_by) => {
-            let prev = state.keyval.get(&key);
-
-            let op = match prev {
-                Some(RespData::Number(val)) => Ok(RespData::Number(val - maybe_by.unwrap_or(1))),
-                Some(_) => Err(RespData::Error("NaN".into())),
-                None => Ok(RespData::Number(-1)),
+            let by = maybe_by.unwrap_or(state.default_incr_step);
+            let current = match state.get(&key) {
+                Some(RespData::Number(val)) => *val,
+                Some(_) => return Err(CoreError::NotANumber),
+                None => 0,
             };
+            let new_val = current.checked_sub(by).ok_or(CoreError::Overflow)?;
 
-            if let Ok(new_val) = op {
-                state.keyval.insert(key, new_val.clone());
-                new_val
-            } else {
-                op.err().unwrap()
+            record_original(state, &key);
+            state.insert(key, RespData::Number(new_val));
+            Ok(RespData::Number(new_val))
+        }
+        Command::Multi => {
+            state.checkpoints.push(HashMap::new());
+            Ok(RespData::ok())
+        }
+        Command::Exec => match state.checkpoints.pop() {
+            Some(checkpoint) => {
+                if let Some(parent) = state.checkpoints.last_mut() {
+                    for (key, prior) in checkpoint {
+                        parent.entry(key).or_insert(prior);
+                    }
+                }
+                Ok(RespData::ok())
             }
+            None => Err(CoreError::NoActiveTransaction),
+        },
+        Command::Discard => match state.checkpoints.pop() {
+            Some(checkpoint) => {
+                if let Some(parent) = state.checkpoints.last_mut() {
+                    for (key, prior) in &checkpoint {
+                        parent.entry(key.clone()).or_insert_with(|| prior.clone());
+                    }
+                }
+
+                for (key, prior) in checkpoint {
+                    match prior {
+                        Some(value) => {
+                            state.insert(key, value);
+                        }
+                        None => {
+                            state.remove(&key);
+                        }
+                    }
+                }
+
+                Ok(RespData::ok())
+            }
+            None => Err(CoreError::NoActiveTransaction),
+        },
+        Command::Save(path) => persistence::save(state, &path)
+            .map(|()| RespData::ok())
+            .map_err(|err| CoreError::Persistence(err.to_string())),
+        Command::Load(path) => persistence::load_into(state, &path)
+            .map(|()| RespData::ok())
+            .map_err(|err| CoreError::Persistence(err.to_string())),
+        Command::Keys(pattern) => {
+            let matches: Vec<RespData> = state
+                .shards
+                .par_iter()
+                .flat_map(|shard| {
+                    shard
+                        .keys()
+                        .filter(|key| glob_match(&pattern, key))
+                        .cloned()
+                        .collect::<Vec<_>>()
+                })
+                .map(RespData::SimpleStr)
+                .collect();
+            Ok(RespData::Array(matches))
         }
-        _ => RespData::Error("Unknown core cmd".into()),
-    };
-    response
+        Command::DbSize => Ok(RespData::Number(state.len() as i64)),
+    }
 }
 #[cfg(test)]
 mod should {
     use super::*;
-    use std::collections::HashMap;
 
     #[test]
     fn set_adds_a_new_key() {
-        let mut state = CoreState {
-            keyval: HashMap::new(),
-        };
+        let mut state = CoreState::default();
 
         let response = core_logic(
             &mut state,
             Command::Set("a".into(), RespData::SimpleStr("hello".into())),
-        );
+        )
+        .unwrap();
 
         assert_eq!(response, RespData::ok());
-        assert_eq!(state.keyval.len(), 1);
-        assert_eq!(
-            state.keyval.get("a"),
-            Some(&RespData::SimpleStr("hello".into()))
-        );
+        assert_eq!(state.len(), 1);
+        assert_eq!(state.get("a"), Some(&RespData::SimpleStr("hello".into())));
     }
 
     #[test]
     fn get_gets_a_key() {
-        let mut inner_keyval = HashMap::new();
-        inner_keyval.insert("a".into(), RespData::SimpleStr("hello".into()));
-
-        let mut state = CoreState {
-            keyval: inner_keyval,
-        };
+        let mut state = CoreState::default();
+        state.insert("a".into(), RespData::SimpleStr("hello".into()));
 
-        let response = core_logic(&mut state, Command::Get("a".into()));
+        let response = core_logic(&mut state, Command::Get("a".into())).unwrap();
 
         assert_eq!(response, RespData::SimpleStr("hello".into()));
-        assert_eq!(state.keyval.len(), 1);
-        assert_eq!(
-            state.keyval.get("a"),
-            Some(&RespData::SimpleStr("hello".into()))
-        );
+        assert_eq!(state.len(), 1);
+        assert_eq!(state.get("a"), Some(&RespData::SimpleStr("hello".into())));
     }
 
     #[test]
     fn get_returns_nil_when_key_is_not_found() {
-        let mut state = CoreState {
-            keyval: HashMap::new(),
-        };
+        let mut state = CoreState::default();
 
-        let response = core_logic(&mut state, Command::Get("a".into()));
+        let response = core_logic(&mut state, Command::Get("a".into())).unwrap();
 
         assert_eq!(response, RespData::Error("(nil)".into()));
-        assert_eq!(state.keyval.len(), 0);
-        assert_eq!(state.keyval.get("a"), None);
+        assert_eq!(state.len(), 0);
+        assert_eq!(state.get("a"), None);
     }
 
     #[test]
     fn set_overwrites_existing_value() {
-        let mut state = CoreState {
-            keyval: HashMap::new(),
-        };
+        let mut state = CoreState::default();
 
         let key: String = "key-a".into();
 
         let response_a = core_logic(
             &mut state,
             Command::Set(key.clone(), RespData::SimpleStr("hello".into())),
-        );
+        )
+        .unwrap();
 
         let response_b = core_logic(
             &mut state,
             Command::Set(key.clone(), RespData::SimpleStr("goodbye".into())),
-        );
+        )
+        .unwrap();
 
         assert_eq!(response_a, RespData::ok());
         assert_eq!(response_b, RespData::ok());
-        assert_eq!(state.keyval.len(), 1);
+        assert_eq!(state.len(), 1);
         assert_eq!(
-            state.keyval.get(&key),
+            state.get(&key),
             Some(&RespData::SimpleStr("goodbye".into()))
         );
     }
 
     #[test]
     fn flushall_deletes_everything() {
-        let mut state = CoreState {
-            keyval: HashMap::new(),
-        };
+        let mut state = CoreState::default();
 
         core_logic(
             &mut state,
             Command::Set("a".into(), RespData::SimpleStr("hello".into())),
-        );
+        )
+        .unwrap();
         core_logic(
             &mut state,
             Command::Set("b".into(), RespData::SimpleStr("goodbye".into())),
-        );
+        )
+        .unwrap();
 
-        assert_eq!(state.keyval.len(), 2);
+        assert_eq!(state.len(), 2);
+        assert_eq!(state.get("a"), Some(&RespData::SimpleStr("hello".into())));
         assert_eq!(
-            state.keyval.get("a"),
-            Some(&RespData::SimpleStr("hello".into()))
-        );
-        assert_eq!(
-            state.keyval.get("b"),
+            state.get("b"),
             Some(&RespData::SimpleStr("goodbye".into()))
         );
 
-        core_logic(&mut state, Command::FlushAll);
+        core_logic(&mut state, Command::FlushAll).unwrap();
 
-        assert_eq!(state.keyval.len(), 0);
-        assert_eq!(state.keyval.get("a"), None);
-        assert_eq!(state.keyval.get("b"), None);
+        assert_eq!(state.len(), 0);
+        assert_eq!(state.get("a"), None);
+        assert_eq!(state.get("b"), None);
     }
 
     #[test]
     fn incr() {
-        let mut state = CoreState {
-            keyval: HashMap::new(),
-        };
+        let mut state = CoreState::default();
 
         // It creates a key when there isn't one
-        let response = core_logic(&mut state, Command::Incr("a".into(), None));
-        assert_eq!(state.keyval.get("a"), Some(&RespData::Number(1)));
+        let response = core_logic(&mut state, Command::Incr("a".into(), None)).unwrap();
+        assert_eq!(state.get("a"), Some(&RespData::Number(1)));
         assert_eq!(response, RespData::Number(1));
 
         // It increments existing keys
-        let response = core_logic(&mut state, Command::Incr("a".into(), None));
-        assert_eq!(state.keyval.get("a"), Some(&RespData::Number(2)));
+        let response = core_logic(&mut state, Command::Incr("a".into(), None)).unwrap();
+        assert_eq!(state.get("a"), Some(&RespData::Number(2)));
         assert_eq!(response, RespData::Number(2));
 
         // It increments by the given amount
-        let response = core_logic(&mut state, Command::Incr("a".into(), Some(10)));
-        assert_eq!(state.keyval.get("a"), Some(&RespData::Number(12)));
+        let response = core_logic(&mut state, Command::Incr("a".into(), Some(10))).unwrap();
+        assert_eq!(state.get("a"), Some(&RespData::Number(12)));
         assert_eq!(response, RespData::Number(12));
     }
 
+    #[test]
+    fn incr_on_a_non_number_returns_not_a_number() {
+        let mut state = CoreState::default();
+        state.insert("a".into(), RespData::SimpleStr("hello".into()));
+
+        let err = core_logic(&mut state, Command::Incr("a".into(), None)).unwrap_err();
+
+        assert_eq!(err, CoreError::NotANumber);
+    }
+
+    #[test]
+    fn incr_that_would_overflow_returns_overflow() {
+        let mut state = CoreState::default();
+        state.insert("a".into(), RespData::Number(i64::MAX));
+
+        let err = core_logic(&mut state, Command::Incr("a".into(), None)).unwrap_err();
+
+        assert_eq!(err, CoreError::Overflow);
+        assert_eq!(state.get("a"), Some(&RespData::Number(i64::MAX)));
+    }
+
     #[test]
     fn decr() {
-        let mut state = CoreState {
-            keyval: HashMap::new(),
-        };
+        let mut state = CoreState::default();
 
         // It creates a key when there isn't one
-        let response = core_logic(&mut state, Command::Decr("a".into(), None));
-        assert_eq!(state.keyval.get("a"), Some(&RespData::Number(-1)));
+        let response = core_logic(&mut state, Command::Decr("a".into(), None)).unwrap();
+        assert_eq!(state.get("a"), Some(&RespData::Number(-1)));
         assert_eq!(response, RespData::Number(-1));
 
         // It decrements existing keys
-        let response = core_logic(&mut state, Command::Decr("a".into(), None));
-        assert_eq!(state.keyval.get("a"), Some(&RespData::Number(-2)));
+        let response = core_logic(&mut state, Command::Decr("a".into(), None)).unwrap();
+        assert_eq!(state.get("a"), Some(&RespData::Number(-2)));
         assert_eq!(response, RespData::Number(-2));
 
         // It decrements by the given amount
-        let response = core_logic(&mut state, Command::Decr("a".into(), Some(10)));
-        assert_eq!(state.keyval.get("a"), Some(&RespData::Number(-12)));
+        let response = core_logic(&mut state, Command::Decr("a".into(), Some(10))).unwrap();
+        assert_eq!(state.get("a"), Some(&RespData::Number(-12)));
         assert_eq!(response, RespData::Number(-12));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn decr_that_would_overflow_returns_overflow() {
+        let mut state = CoreState::default();
+        state.insert("a".into(), RespData::Number(i64::MIN));
+
+        let err = core_logic(&mut state, Command::Decr("a".into(), None)).unwrap_err();
+
+        assert_eq!(err, CoreError::Overflow);
+        assert_eq!(state.get("a"), Some(&RespData::Number(i64::MIN)));
+    }
+
+    #[test]
+    fn exec_keeps_changes_made_inside_multi() {
+        let mut state = CoreState::default();
+
+        core_logic(&mut state, Command::Multi).unwrap();
+        core_logic(
+            &mut state,
+            Command::Set("a".into(), RespData::SimpleStr("hello".into())),
+        )
+        .unwrap();
+        core_logic(&mut state, Command::Exec).unwrap();
+
+        assert_eq!(state.checkpoints.len(), 0);
+        assert_eq!(state.get("a"), Some(&RespData::SimpleStr("hello".into())));
+    }
+
+    #[test]
+    fn discard_removes_a_key_that_did_not_exist_before_multi() {
+        let mut state = CoreState::default();
+
+        core_logic(&mut state, Command::Multi).unwrap();
+        core_logic(
+            &mut state,
+            Command::Set("a".into(), RespData::SimpleStr("hello".into())),
+        )
+        .unwrap();
+        core_logic(&mut state, Command::Discard).unwrap();
+
+        assert_eq!(state.checkpoints.len(), 0);
+        assert_eq!(state.get("a"), None);
+    }
+
+    #[test]
+    fn discard_restores_a_key_that_existed_before_multi() {
+        let mut state = CoreState::default();
+        state.insert("a".into(), RespData::Number(1));
+
+        core_logic(&mut state, Command::Multi).unwrap();
+        core_logic(&mut state, Command::Incr("a".into(), Some(10))).unwrap();
+        assert_eq!(state.get("a"), Some(&RespData::Number(11)));
+
+        core_logic(&mut state, Command::Discard).unwrap();
+
+        assert_eq!(state.get("a"), Some(&RespData::Number(1)));
+    }
+
+    #[test]
+    fn discard_of_nested_checkpoint_merges_originals_into_parent() {
+        let mut state = CoreState::default();
+        state.insert("a".into(), RespData::Number(1));
+
+        core_logic(&mut state, Command::Multi).unwrap();
+        core_logic(&mut state, Command::Incr("a".into(), Some(10))).unwrap(); // a: 11, outer checkpoint remembers 1
+
+        core_logic(&mut state, Command::Multi).unwrap();
+        core_logic(&mut state, Command::Incr("a".into(), Some(100))).unwrap(); // a: 111, inner checkpoint remembers 11
+        core_logic(&mut state, Command::Discard).unwrap(); // a back to 11, outer checkpoint still remembers 1
+
+        assert_eq!(state.get("a"), Some(&RespData::Number(11)));
+        assert_eq!(state.checkpoints.len(), 1);
+
+        core_logic(&mut state, Command::Discard).unwrap(); // a back to 1
+
+        assert_eq!(state.get("a"), Some(&RespData::Number(1)));
+        assert_eq!(state.checkpoints.len(), 0);
+    }
+
+    #[test]
+    fn discard_without_multi_returns_no_active_transaction() {
+        let mut state = CoreState::default();
+
+        let err = core_logic(&mut state, Command::Discard).unwrap_err();
+
+        assert_eq!(err, CoreError::NoActiveTransaction);
+    }
+
+    #[test]
+    fn exec_without_multi_returns_no_active_transaction() {
+        let mut state = CoreState::default();
+
+        let err = core_logic(&mut state, Command::Exec).unwrap_err();
+
+        assert_eq!(err, CoreError::NoActiveTransaction);
+    }
+
+    #[test]
+    fn discard_of_outer_multi_rolls_back_a_key_committed_by_an_inner_exec() {
+        let mut state = CoreState::default();
+
+        core_logic(&mut state, Command::Multi).unwrap();
+        core_logic(&mut state, Command::Multi).unwrap();
+        core_logic(
+            &mut state,
+            Command::Set("b".into(), RespData::SimpleStr("hello".into())),
+        )
+        .unwrap();
+        core_logic(&mut state, Command::Exec).unwrap(); // commits the inner transaction
+
+        assert_eq!(state.checkpoints.len(), 1);
+        assert_eq!(state.get("b"), Some(&RespData::SimpleStr("hello".into())));
+
+        core_logic(&mut state, Command::Discard).unwrap(); // rolls back the outer transaction
+
+        assert_eq!(state.checkpoints.len(), 0);
+        assert_eq!(state.get("b"), None);
+    }
+
+    #[test]
+    fn save_then_load_restores_the_keyspace() {
+        let path = std::env::temp_dir().join("rustdss-base-logic-save-then-load.json");
+        let path = path.to_str().unwrap().to_string();
+
+        let mut state = CoreState::default();
+        core_logic(
+            &mut state,
+            Command::Set("a".into(), RespData::SimpleStr("hello".into())),
+        )
+        .unwrap();
+        core_logic(&mut state, Command::Save(path.clone())).unwrap();
+
+        let mut fresh_state = CoreState::default();
+        let response = core_logic(&mut fresh_state, Command::Load(path.clone())).unwrap();
+
+        assert_eq!(response, RespData::ok());
+        assert_eq!(
+            fresh_state.get("a"),
+            Some(&RespData::SimpleStr("hello".into()))
+        );
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn load_replaces_keys_not_present_in_the_snapshot() {
+        let path = std::env::temp_dir().join("rustdss-base-logic-load-replaces.json");
+        let path = path.to_str().unwrap().to_string();
+
+        let mut state = CoreState::default();
+        core_logic(
+            &mut state,
+            Command::Set("a".into(), RespData::SimpleStr("hello".into())),
+        )
+        .unwrap();
+        core_logic(&mut state, Command::Save(path.clone())).unwrap();
+
+        core_logic(
+            &mut state,
+            Command::Set("b".into(), RespData::SimpleStr("goodbye".into())),
+        )
+        .unwrap();
+        assert_eq!(state.len(), 2);
+
+        core_logic(&mut state, Command::Load(path.clone())).unwrap();
+
+        assert_eq!(state.len(), 1);
+        assert_eq!(state.get("a"), Some(&RespData::SimpleStr("hello".into())));
+        assert_eq!(state.get("b"), None);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn dbsize_counts_keys_across_all_shards() {
+        let mut state = CoreState::default();
+        core_logic(
+            &mut state,
+            Command::Set("a".into(), RespData::SimpleStr("hello".into())),
+        )
+        .unwrap();
+        core_logic(
+            &mut state,
+            Command::Set("b".into(), RespData::SimpleStr("world".into())),
+        )
+        .unwrap();
+
+        let response = core_logic(&mut state, Command::DbSize).unwrap();
+
+        assert_eq!(response, RespData::Number(2));
+    }
+
+    #[test]
+    fn keys_matches_a_glob_pattern_across_all_shards() {
+        let mut state = CoreState::default();
+        core_logic(
+            &mut state,
+            Command::Set("user:1".into(), RespData::SimpleStr("a".into())),
+        )
+        .unwrap();
+        core_logic(
+            &mut state,
+            Command::Set("user:2".into(), RespData::SimpleStr("b".into())),
+        )
+        .unwrap();
+        core_logic(
+            &mut state,
+            Command::Set("post:1".into(), RespData::SimpleStr("c".into())),
+        )
+        .unwrap();
+
+        let response = core_logic(&mut state, Command::Keys("user:*".into())).unwrap();
+
+        match response {
+            RespData::Array(mut keys) => {
+                keys.sort_by_key(|key| match key {
+                    RespData::SimpleStr(value) => value.clone(),
+                    _ => unreachable!(),
+                });
+                assert_eq!(
+                    keys,
+                    vec![
+                        RespData::SimpleStr("user:1".into()),
+                        RespData::SimpleStr("user:2".into()),
+                    ]
+                );
+            }
+            other => panic!("expected an array, got {other:?}"),
+        }
+    }
+}
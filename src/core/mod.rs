@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+use crate::config::Config;
+use crate::constants;
+use crate::transport::RespData;
+
+pub mod base_logic;
+pub mod persistence;
+
+/// Failures that can arise from command *semantics*, as opposed to a
+/// normal stored value (e.g. a GET miss). Transport formatting of these
+/// into a RESP error string is the connection layer's job, not core_logic's.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CoreError {
+    NotANumber,
+    Overflow,
+    UnknownCommand,
+    NoActiveTransaction,
+    Persistence(String),
+}
+
+impl fmt::Display for CoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoreError::NotANumber => write!(f, "NaN"),
+            CoreError::Overflow => write!(f, "increment or decrement would overflow"),
+            CoreError::UnknownCommand => write!(f, "unknown core cmd"),
+            CoreError::NoActiveTransaction => write!(f, "EXEC/DISCARD without MULTI"),
+            CoreError::Persistence(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+/// Default shard count: used by `CoreState::default()` (e.g. tests) and as
+/// the built-in default for `Config::shard_count`, which `Core::start` uses.
+pub const SHARD_COUNT: usize = 16;
+
+pub struct CoreState {
+    pub shards: Vec<HashMap<String, RespData>>,
+    pub checkpoints: Vec<HashMap<String, Option<RespData>>>,
+    pub default_incr_step: i64,
+}
+
+impl Default for CoreState {
+    fn default() -> Self {
+        CoreState::new(SHARD_COUNT, constants::DEFAULT_INCR_STEP)
+    }
+}
+
+impl CoreState {
+    pub fn new(shard_count: usize, default_incr_step: i64) -> Self {
+        CoreState {
+            shards: (0..shard_count.max(1)).map(|_| HashMap::new()).collect(),
+            checkpoints: Vec::new(),
+            default_incr_step,
+        }
+    }
+
+    pub fn shard_index(&self, key: &str) -> usize {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        (hasher.finish() as usize) % self.shards.len()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&RespData> {
+        self.shards[self.shard_index(key)].get(key)
+    }
+
+    pub fn insert(&mut self, key: String, value: RespData) -> Option<RespData> {
+        let idx = self.shard_index(&key);
+        self.shards[idx].insert(key, value)
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<RespData> {
+        let idx = self.shard_index(key);
+        self.shards[idx].remove(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(HashMap::len).sum()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Set(String, RespData),
+    Get(String),
+    FlushAll,
+    Incr(String, Option<i64>),
+    Decr(String, Option<i64>),
+    Multi,
+    Exec,
+    Discard,
+    Save(String),
+    Load(String),
+    Keys(String),
+    DbSize,
+}
+
+pub struct Core {
+    sender: Sender<(Command, Sender<Result<RespData, CoreError>>)>,
+}
+
+impl Core {
+    pub fn start(config: &Config) -> Self {
+        let (sender, receiver) =
+            mpsc::channel::<(Command, Sender<Result<RespData, CoreError>>)>();
+        let shard_count = config.shard_count;
+        let default_incr_step = config.default_incr_step;
+        let snapshot_path = config.snapshot_path.clone();
+
+        thread::spawn(move || {
+            let mut state = CoreState::new(shard_count, default_incr_step);
+            let _ = persistence::load_into(&mut state, &snapshot_path);
+
+            for (cmd, reply_to) in receiver {
+                let response = base_logic::core_logic(&mut state, cmd);
+                let _ = reply_to.send(response);
+            }
+        });
+
+        Core { sender }
+    }
+
+    pub fn get_sender(&self) -> Sender<(Command, Sender<Result<RespData, CoreError>>)> {
+        self.sender.clone()
+    }
+}
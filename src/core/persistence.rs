@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+
+use crate::transport::RespData;
+
+use super::CoreState;
+
+/// Writes the whole key space to `path` as a single JSON document, the way
+/// SAVE/BGSAVE dump the dataset in other Redis-style stores. The shard split
+/// is an in-memory routing detail, so the snapshot is flattened back into
+/// one map and re-sharded on load.
+pub fn save(state: &CoreState, path: &str) -> io::Result<()> {
+    let flattened: HashMap<&String, &RespData> = state
+        .shards
+        .iter()
+        .flat_map(|shard| shard.iter())
+        .collect();
+
+    let serialized = serde_json::to_string(&flattened)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    fs::write(path, serialized)
+}
+
+/// Loads `path` into an already-constructed `state`, preserving its shard
+/// count and configured `default_incr_step`. This replaces the key space
+/// rather than merging into it, the same way LOAD replaces a real Redis-style
+/// dataset: keys already in `state` but absent from the snapshot are gone
+/// afterwards.
+pub fn load_into(state: &mut CoreState, path: &str) -> io::Result<()> {
+    let contents = fs::read_to_string(path)?;
+    let flattened: HashMap<String, RespData> = serde_json::from_str(&contents)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+    for shard in &mut state.shards {
+        shard.clear();
+    }
+
+    for (key, value) in flattened {
+        state.insert(key, value);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,15 @@
+mod config;
+mod connection;
+mod constants;
+mod core;
+mod request;
+mod transport;
+
+fn main() -> Result<(), std::io::Error> {
+    let config = config::Config::load(Some(constants::CONFIG_PATH)).expect("invalid configuration");
+
+    let core = core::Core::start(&config);
+    connection::Connection::start(core.get_sender(), &config)?;
+
+    Ok(())
+}
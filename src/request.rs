@@ -0,0 +1,93 @@
+use crate::core::{Command, CoreError};
+use crate::transport::RespData;
+
+pub fn parse(line: &str) -> Result<Command, CoreError> {
+    let mut parts = line.split_whitespace();
+
+    let verb = parts.next().ok_or(CoreError::UnknownCommand)?;
+    let mut arg = || parts.next().ok_or(CoreError::UnknownCommand);
+
+    match verb.to_ascii_uppercase().as_str() {
+        "SET" => Ok(Command::Set(
+            arg()?.into(),
+            RespData::SimpleStr(arg()?.into()),
+        )),
+        "GET" => Ok(Command::Get(arg()?.into())),
+        "FLUSHALL" => Ok(Command::FlushAll),
+        "INCR" => Ok(Command::Incr(arg()?.into(), optional_amount(&mut parts)?)),
+        "DECR" => Ok(Command::Decr(arg()?.into(), optional_amount(&mut parts)?)),
+        "MULTI" => Ok(Command::Multi),
+        "EXEC" => Ok(Command::Exec),
+        "DISCARD" => Ok(Command::Discard),
+        "SAVE" => Ok(Command::Save(arg()?.into())),
+        "LOAD" => Ok(Command::Load(arg()?.into())),
+        "KEYS" => Ok(Command::Keys(arg()?.into())),
+        "DBSIZE" => Ok(Command::DbSize),
+        _ => Err(CoreError::UnknownCommand),
+    }
+}
+
+// INCR/DECR take an optional amount as their second argument; anything
+// present that doesn't parse as an i64 is a NotANumber, the same error
+// core_logic returns for incrementing a non-numeric value.
+fn optional_amount(parts: &mut std::str::SplitWhitespace) -> Result<Option<i64>, CoreError> {
+    parts
+        .next()
+        .map(|value| value.parse().map_err(|_| CoreError::NotANumber))
+        .transpose()
+}
+
+#[cfg(test)]
+mod should {
+    use super::*;
+
+    #[test]
+    fn set_parses_key_and_value() {
+        assert_eq!(
+            parse("SET a hello").unwrap(),
+            Command::Set("a".into(), RespData::SimpleStr("hello".into()))
+        );
+    }
+
+    #[test]
+    fn incr_without_an_amount_parses_to_none() {
+        assert_eq!(parse("INCR a").unwrap(), Command::Incr("a".into(), None));
+    }
+
+    #[test]
+    fn incr_with_an_amount_parses_it() {
+        assert_eq!(
+            parse("INCR a 10").unwrap(),
+            Command::Incr("a".into(), Some(10))
+        );
+    }
+
+    #[test]
+    fn decr_with_an_amount_parses_it() {
+        assert_eq!(
+            parse("DECR a 10").unwrap(),
+            Command::Decr("a".into(), Some(10))
+        );
+    }
+
+    #[test]
+    fn incr_with_a_non_number_amount_is_not_a_number() {
+        let err = parse("INCR a not-a-number").unwrap_err();
+
+        assert_eq!(err, CoreError::NotANumber);
+    }
+
+    #[test]
+    fn unknown_verb_is_unknown_command() {
+        let err = parse("FROBNICATE a").unwrap_err();
+
+        assert_eq!(err, CoreError::UnknownCommand);
+    }
+
+    #[test]
+    fn missing_required_argument_is_unknown_command() {
+        let err = parse("GET").unwrap_err();
+
+        assert_eq!(err, CoreError::UnknownCommand);
+    }
+}
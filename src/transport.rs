@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RespData {
+    SimpleStr(String),
+    Error(String),
+    Number(i64),
+    Array(Vec<RespData>),
+}
+
+impl RespData {
+    pub fn ok() -> Self {
+        RespData::SimpleStr("OK".into())
+    }
+
+    /// Encodes this value as a RESP reply, the wire format a connected
+    /// client actually reads.
+    pub fn to_resp_string(&self) -> String {
+        match self {
+            RespData::SimpleStr(value) => format!("+{value}\r\n"),
+            RespData::Error(message) => format!("-{message}\r\n"),
+            RespData::Number(value) => format!(":{value}\r\n"),
+            RespData::Array(items) => {
+                let mut encoded = format!("*{}\r\n", items.len());
+                for item in items {
+                    encoded.push_str(&item.to_resp_string());
+                }
+                encoded
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod should {
+    use super::*;
+
+    #[test]
+    fn simple_str_encodes_with_a_plus_prefix() {
+        assert_eq!(RespData::ok().to_resp_string(), "+OK\r\n");
+    }
+
+    #[test]
+    fn error_encodes_with_a_minus_prefix() {
+        assert_eq!(
+            RespData::Error("oops".into()).to_resp_string(),
+            "-oops\r\n"
+        );
+    }
+
+    #[test]
+    fn number_encodes_with_a_colon_prefix() {
+        assert_eq!(RespData::Number(42).to_resp_string(), ":42\r\n");
+    }
+
+    #[test]
+    fn array_encodes_its_length_then_each_element() {
+        let array = RespData::Array(vec![RespData::Number(1), RespData::ok()]);
+
+        assert_eq!(array.to_resp_string(), "*2\r\n:1\r\n+OK\r\n");
+    }
+}